@@ -3,19 +3,22 @@ use std::str::FromStr;
 use web3::api::Eth;
 use web3::contract::{Contract, Options};
 use web3::transports::WebSocket;
-use web3::types::{Address, Bytes, U256};
+use web3::types::{Address, Bytes, H256, U256};
 use web3::{Error, signing, Web3};
-use crate::MerkleTree;
 use crate::config;
 use crate::mt::AsBytes;
+use crate::mt::IncrementalMerkleTree;
+use crate::mpt::Trie;
 
+/// depth of the incremental whitelist tree, large enough that it never needs resizing
+const WHITELIST_TREE_DEPTH: usize = 32;
 
 /// [`ContractIwf`] Structure - to handle contract interaction
 #[derive!(Debug)]
 pub struct ContractIwf {
     web3_instance: web3::Web3<WebSocket>, // web3 instance
     owner_address: Vec<u8>,
-    mt: MerkleTree, // merkletree instance
+    imt: IncrementalMerkleTree, // frontier of the whitelist tree, updated on every `add_address`
     contract: Contract<WebSocket>, // contract instance
     whitelist_addresses: Vec<[u8]>,
     root_hash: Vec<u8>,
@@ -35,24 +38,25 @@ impl ContractIwf {
         Ok(ContractIwf {
             web3_instance: instance,
             owner_address: owner_address.to_vec(),
-            mt: MerkleTree::build(&[], true),
+            imt: IncrementalMerkleTree::new(WHITELIST_TREE_DEPTH, true),
             contract: contract,
             whitelist_addresses: Vec::new(),
             root_hash: "".to_vec(),
         })
     }
 
-    /// [`ContractIwf::add_address`] - add address to internal `MerkleTree` and get the root hash
+    /// [`ContractIwf::add_address`] - append address to the incremental whitelist tree and get the root hash
     /// and update SmartContract root hash
+    /// Unlike a full `MerkleTree::build`, this only walks the frontier of the tree (O(log n))
+    /// instead of re-hashing every whitelisted address on each call
     pub fn add_address(&mut self, mut address: &str, secret_key: &str) -> String {
         if address.starts_with("0x") {
             address = &address[2..];
         }
 
         self.whitelist_addresses.as_mut().push(*address.as_byte_slice());
-        // build merkletree
-        self.mt = MerkleTree::build(self.whitelist_addresses.iter().map(|s| s as &str).collect(), true);
-        self.root_hash = self.mt.root_hash_str().to_vec();
+        self.imt.append(address);
+        self.root_hash = self.imt.root_hash_str().to_vec();
 
         let c = Bytes::from(&self.root_hash);
 
@@ -77,13 +81,28 @@ impl ContractIwf {
         }
         let val = U256::from(value);
 
-        // get proofs
-        let proofs =  self.mt.proof(address, -1).iter().map(|v| Bytes::from(v)).collect();
+        // the proof is generated from the same incremental tree `add_address` committed the root
+        // of, not a freshly-sorted `MerkleTree` built over a different padding/ordering convention
+        // -- otherwise it would never validate against the root already pushed to the contract
+        let leaves: Vec<&str> = self.whitelist_addresses.iter().map(|s| s as &str).collect();
+        let index = leaves.iter().position(|a| *a == address).expect("address is not whitelisted");
+
+        // get proofs, the contract only needs the sibling hashes since it knows the leaf's own index on-chain
+        let proofs = self.imt.proof(&leaves, index).iter().map(|(hash, _side)| Bytes::from(hash.clone())).collect();
 
         let seckey: secp256k1::key::SecretKey = secret_key.parse().unwrap();
         let tx_hash = self.contract.signed_call("updateMTRoot", (val, proofs), Options::default(), seckey).await?;
         tx_hash.to_string()
     }
+
+    /// [`ContractIwf::verify_account_state`] checks `expected_value` (the raw value stored under `key`,
+    /// e.g. an RLP-encoded account or storage slot value, compared byte for byte) against a block's
+    /// `state_root` using an `eth_getProof` witness, trustlessly — unlike
+    /// `get_state`, it does not depend on the node answering `query` honestly
+    pub fn verify_account_state(&self, state_root: H256, proof_nodes: &[Bytes], key: &[u8], expected_value: Option<&[u8]>) -> bool {
+        let trie = Trie::from_proof(state_root, proof_nodes);
+        trie.verify(key, expected_value)
+    }
 }
 
 /// Just simple test for `ContractIwf`
@@ -7,6 +7,7 @@ use crate::hex::FromHex;
 use crate::mt::MerkleTree;
 
 mod mt;
+mod mpt;
 mod utils;
 mod blockchain;
 mod config;
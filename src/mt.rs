@@ -2,18 +2,75 @@ extern crate crypto;
 
 use std::fmt;
 use std::fmt::Formatter;
+use std::io::{Read, Write};
 use std::ptr::hash;
 use rustc_serialize::hex::ToHex;
 use ethers::utils::{hex, keccak256};
 use ethers::utils::hex::FromHex;
+use starknet_crypto::{poseidon_hash, FieldElement};
 
 use crate::utils;
 
 const LEAF_SIG: u8 = 0u8;
 const INTERNAL_SIG: u8 = 1u8;
 
+const LEAVES_FORMAT_VERSION: u8 = 1;
+const FRONTIER_FORMAT_VERSION: u8 = 1;
+
 type Hash = Vec<u8>;
 
+/// [`MerkleHasher`] abstracts the leaf/node hashing used by [`MerkleTree`] and
+/// [`IncrementalMerkleTree`], so a tree can be built over keccak256 (the EVM
+/// default, see [`Keccak256Hasher`]) or over a hasher that is cheaper to verify
+/// in a SNARK/STARK circuit, such as [`PoseidonHasher`]
+pub trait MerkleHasher: fmt::Debug {
+    /// hashes the raw bytes of a single leaf value
+    fn hash_leaf(&self, data: &[u8]) -> Hash;
+    /// hashes a pair of child nodes into their parent
+    fn hash_nodes(&self, left: &Hash, right: &Hash) -> Hash;
+}
+
+/// [`Keccak256Hasher`] the default hasher, matching the keccak256 hashing `set_state` expects on-chain
+#[derive(Debug, Default)]
+pub struct Keccak256Hasher;
+
+impl MerkleHasher for Keccak256Hasher {
+    fn hash_leaf(&self, data: &[u8]) -> Hash {
+        keccak256(data.to_vec()).to_vec()
+    }
+
+    fn hash_nodes(&self, left: &Hash, right: &Hash) -> Hash {
+        let temp = [&left[..], &right[..]].concat();
+        keccak256(temp).to_vec()
+    }
+}
+
+/// [`PoseidonHasher`] hashes with Poseidon over the StarkNet field, so a tree built with it
+/// produces roots a Cairo contract can verify directly, unlike the keccak256 roots `Keccak256Hasher` produces
+#[derive(Debug, Default)]
+pub struct PoseidonHasher;
+
+impl MerkleHasher for PoseidonHasher {
+    fn hash_leaf(&self, data: &[u8]) -> Hash {
+        let fe = FieldElement::from_byte_slice_be(data).expect("leaf value does not fit in a StarkNet field element");
+        poseidon_hash(fe, FieldElement::ZERO).to_bytes_be().to_vec()
+    }
+
+    fn hash_nodes(&self, left: &Hash, right: &Hash) -> Hash {
+        let l = FieldElement::from_byte_slice_be(left).expect("hash does not fit in a StarkNet field element");
+        let r = FieldElement::from_byte_slice_be(right).expect("hash does not fit in a StarkNet field element");
+        poseidon_hash(l, r).to_bytes_be().to_vec()
+    }
+}
+
+/// [`Side`] tells a proof verifier which side of the pair a sibling hash sits on,
+/// so it knows whether to combine it as `(sibling, node)` or `(node, sibling)`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
 /// [`MerkelTree`] structure definition
 #[derive(Debug)]
 pub struct MerkleTree {
@@ -21,53 +78,39 @@ pub struct MerkleTree {
     count_internal_nodes: usize,
     count_leaves: usize,
     sort: bool,
+    hasher: Box<dyn MerkleHasher>,
 }
 
 /// [`hash_leaf`] function to hash leaves node
-fn hash_leaf(value: &str) -> Hash {
-    let mut result = vec![0u8; 32];
-
-    let a = Vec::from_hex(value).expect("Invalid hex string");
-    // println!(">>>> {:?}", &a);
-    result = keccak256(a).to_vec();
-    result
+fn hash_leaf(hasher: &dyn MerkleHasher, value: &str) -> Hash {
+    let data = Vec::from_hex(value).expect("Invalid hex string");
+    hasher.hash_leaf(&data)
 }
 
 /// [`hash_internal_nodes`] which get left and right node and make node
 /// If right is None --> Then just left node returns
 /// If sort parameter is true and right node is not None --> then the pair is sorted first and then will be hashed
-fn hash_internal_nodes(left: &Hash, right: Option<&Hash>, sort: bool) -> Hash {
-    let mut result = vec![0u8; 32];
-
-    let mut temp = vec![0u8; left.len() * 2];
-    if let Some(r) = right {
-        let mut p: Vec<Hash> = Vec::new();
-        p.push((&left).to_vec());
-        p.push((&r).to_vec());
-
-        if sort {
-            p.sort();
+fn hash_internal_nodes(hasher: &dyn MerkleHasher, left: &Hash, right: Option<&Hash>, sort: bool) -> Hash {
+    match right {
+        Some(r) => {
+            let (a, b) = if sort && left > r { (r, left) } else { (left, r) };
+            hasher.hash_nodes(a, b)
         }
-        temp = [&p[0][..], &p[1][..]].concat();
-        result = keccak256(temp).to_vec();
-    } else {
-        result = left.clone();
+        None => left.clone(),
     }
-    // println!("{:?}", result.to_hex());
-    result
 }
 
 /// [`build_upper_level`] loop through hashed nodes and make the upper level nodes
-fn build_upper_level(nodes: &[Hash], sort: bool) -> Vec<Hash> {
+fn build_upper_level(hasher: &dyn MerkleHasher, nodes: &[Hash], sort: bool) -> Vec<Hash> {
     let mut row = Vec::with_capacity((nodes.len() + 1) / 2);
     let mut i = 0;
 
     while i < nodes.len() {
         if i + 1 < nodes.len() {
-            row.push(hash_internal_nodes(&nodes[i], Some(&nodes[i + 1]), sort));
+            row.push(hash_internal_nodes(hasher, &nodes[i], Some(&nodes[i + 1]), sort));
             i += 2;
         } else {
-            row.push(hash_internal_nodes(&nodes[i], None, sort));
+            row.push(hash_internal_nodes(hasher, &nodes[i], None, sort));
             i += 1;
         }
     }
@@ -81,15 +124,15 @@ fn build_upper_level(nodes: &[Hash], sort: bool) -> Vec<Hash> {
 }
 
 /// [`build_internal_nodes`] loop through initial nodes and make the tree till just root left
-fn build_internal_nodes(nodes: &mut Vec<Vec<u8>>, count_internal_nodes: usize, sort: bool) {
-    let mut parents = build_upper_level(&nodes[count_internal_nodes..], sort);
+fn build_internal_nodes(hasher: &dyn MerkleHasher, nodes: &mut Vec<Vec<u8>>, count_internal_nodes: usize, sort: bool) {
+    let mut parents = build_upper_level(hasher, &nodes[count_internal_nodes..], sort);
 
     let mut upper_level_start = count_internal_nodes - parents.len();
     let mut upper_level_end = upper_level_start + parents.len();
     nodes[upper_level_start..upper_level_end].clone_from_slice(&parents);
 
     while parents.len() > 1{
-        parents = build_upper_level(parents.as_slice(), sort);
+        parents = build_upper_level(hasher, parents.as_slice(), sort);
 
         upper_level_start -= parents.len();
         upper_level_end = upper_level_start + parents.len();
@@ -99,45 +142,68 @@ fn build_internal_nodes(nodes: &mut Vec<Vec<u8>>, count_internal_nodes: usize, s
     nodes[0] = parents.remove(0);
 }
 
+/// [`write_len_prefixed`] writes `data` as a u64 LE length prefix followed by the bytes themselves
+fn write_len_prefixed(w: &mut impl Write, data: &[u8]) -> std::io::Result<()> {
+    w.write_all(&(data.len() as u64).to_le_bytes())?;
+    w.write_all(data)
+}
+
+/// [`read_u64`] reads a u64 LE integer, the width every length prefix and count in these formats uses
+fn read_u64(r: &mut impl Read) -> std::io::Result<u64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// [`read_len_prefixed`] reads back a `write_len_prefixed` value
+fn read_len_prefixed(r: &mut impl Read) -> std::io::Result<Vec<u8>> {
+    let len = read_u64(r)? as usize;
+    let mut data = vec![0u8; len];
+    r.read_exact(&mut data)?;
+    Ok(data)
+}
+
 /// [`calculate_internal_nodes_count`] just calculate the space needed for all tree nodes plus all internal nodes
 fn calculate_internal_nodes_count(count_leaves: usize) -> usize {
     utils::next_power_of_2(count_leaves) - 1
 }
 
 /// [`_build_from_leaves`] the internal function that hash leaves and make the new `MerkleTree`
-fn _build_from_leaves(leaves: &[Hash], sort: bool) -> MerkleTree {
+fn _build_from_leaves(leaves: &[Hash], sort: bool, hasher: Box<dyn MerkleHasher>) -> MerkleTree {
     let count_leaves = leaves.len();
     let count_internal_nodes = calculate_internal_nodes_count(count_leaves);
     let mut nodes = vec![Vec::new(); count_internal_nodes + count_leaves];
 
     nodes[count_internal_nodes..].clone_from_slice(leaves);
 
-    build_internal_nodes(&mut nodes, count_internal_nodes, sort);
+    build_internal_nodes(hasher.as_ref(), &mut nodes, count_internal_nodes, sort);
 
     MerkleTree {
         sort: sort,
         nodes: nodes,
         count_internal_nodes: count_internal_nodes,
         count_leaves: count_leaves,
+        hasher: hasher,
     }
 }
 
 /// [`MerkleTree`] implementation
 impl MerkleTree  {
-    /// [`MerkleTree::build`] to build `MerkleTree` from nodes
+    /// [`MerkleTree::build`] to build `MerkleTree` from nodes, hashing with the default [`Keccak256Hasher`]
     pub fn build(values: &[&str], sort: bool) -> MerkleTree {
-        MerkleTree::build_with_hasher(values, sort)
+        MerkleTree::build_with_hasher(values, sort, Box::new(Keccak256Hasher))
     }
 
-    pub fn build_with_hasher(values: &[&str], sort: bool) -> MerkleTree {
+    /// [`MerkleTree::build_with_hasher`] to build `MerkleTree` from nodes using the given [`MerkleHasher`]
+    pub fn build_with_hasher(values: &[&str], sort: bool, hasher: Box<dyn MerkleHasher>) -> MerkleTree {
         let count_leaves = values.len();
         assert!(count_leaves > 1, "expected more than 1 value, received {}", count_leaves);
-        let mut leaves: Vec<Hash> = values.iter().map(|v| hash_leaf(v)).collect();
+        let mut leaves: Vec<Hash> = values.iter().map(|v| hash_leaf(hasher.as_ref(), v)).collect();
 
         if sort {
             leaves.sort();
         }
-        _build_from_leaves(leaves.as_slice(), sort)
+        _build_from_leaves(leaves.as_slice(), sort, hasher)
     }
 
     /// [`MerkleTree::root_hash`] to return root hash as array
@@ -157,30 +223,459 @@ impl MerkleTree  {
     }
 
     /// [`MerkleTree::proof`] get `leaf` and `index` and returns the inclusion-proof of `MerkleTree`
-    pub fn proof(&mut self,leaf: &str, mut index: isize) -> Vec<&Hash> {
+    /// Each entry carries the [`Side`] the sibling sits on, so [`verify_proof`] can fold the
+    /// pair in the right order without needing to re-derive it from the leaf index.
+    ///
+    /// Walks freshly-built `layers` (see `build_layers`) rather than indexing into the packed
+    /// `nodes` array: `nodes`'s layout assumes every level shrinks by exactly half, which isn't
+    /// true once `build_upper_level`'s odd-row duplicate-last-node padding kicks in, and indexing
+    /// through that assumption picks up hashes from the wrong level entirely for most leaf counts.
+    pub fn proof(&mut self, leaf: &str, mut index: isize) -> Vec<(Hash, Side)> {
         if index == -1 {
-            if  self.nodes[self.count_internal_nodes..].contains(&hash_leaf(leaf)) {
-                index = self.nodes.iter().position(|r| r == &hash_leaf(leaf)).unwrap() as isize;
+            let leaf_hash = hash_leaf(self.hasher.as_ref(), leaf);
+            if let Some(pos) = self.leaves().iter().position(|r| r == &leaf_hash) {
+                index = pos as isize;
             }
         }
-        if index <= -1 {
+        if index < 0 || index as usize >= self.count_leaves {
             return Vec::new();
         }
 
-        let mut proof: Vec<&Hash> = Vec::new();
-        while index != 0 {
-            let is_right_node = index % 2;
-            let pair_index = if is_right_node == 0  {index - 1} else { index +1};
+        let layers = build_layers(self.hasher.as_ref(), self.leaves(), self.sort);
+        let mut idx = index as usize;
+        let mut proof: Vec<(Hash, Side)> = Vec::new();
+
+        for level in &layers[..layers.len() - 1] {
+            let sibling_index = idx ^ 1;
+            if let Some(sibling) = level.get(sibling_index) {
+                let side = if idx % 2 == 0 { Side::Right } else { Side::Left };
+                proof.push((sibling.clone(), side));
+            }
+            idx /= 2;
+        }
+
+        proof
+    }
 
-            if pair_index >= 0 &&( pair_index as usize) < self.nodes.len() {
-                proof.push(&self.nodes[pair_index as usize])
+    /// [`MerkleTree::proof_multi`] builds a single [`PartialProof`] that proves every leaf in `indices`
+    /// at once, sharing internal nodes common to more than one of them instead of returning
+    /// one `proof` per address, exactly like Bitcoin's `merkleblock` partial trees
+    pub fn proof_multi(&self, indices: &[usize]) -> PartialProof {
+        let leaves = self.leaves().to_vec();
+        let total_leaves = leaves.len();
+
+        let mut leaf_matched = vec![false; total_leaves];
+        for &index in indices {
+            if index < total_leaves {
+                leaf_matched[index] = true;
             }
+        }
+
+        let layers = build_layers(self.hasher.as_ref(), &leaves, self.sort);
+        let match_layers = build_match_layers(leaf_matched);
 
-            index = ((index - 1) / 2) | 0;
+        let mut bits = Vec::new();
+        let mut hashes = Vec::new();
+        traverse_multi(&layers, &match_layers, layers.len() - 1, 0, &mut bits, &mut hashes);
+
+        PartialProof { bits: bits, hashes: hashes, total_leaves: total_leaves }
+    }
+
+    /// [`MerkleTree::write_leaves`] serializes just the leaf hashes — everything else
+    /// (`count_internal_nodes`, the internal node hashes, ...) is cheaply re-derived by
+    /// `_build_from_leaves` — so a snapshot can be reloaded without re-hashing the original values.
+    /// Format: version byte, a sort byte, a u64 leaf count, then each leaf length-prefixed
+    pub fn write_leaves(&self, w: &mut impl Write) -> std::io::Result<()> {
+        w.write_all(&[LEAVES_FORMAT_VERSION])?;
+        w.write_all(&[self.sort as u8])?;
+        w.write_all(&(self.count_leaves as u64).to_le_bytes())?;
+
+        for leaf in self.leaves() {
+            write_len_prefixed(w, leaf)?;
+        }
+
+        Ok(())
+    }
+
+    /// [`MerkleTree::read_leaves`] rebuilds a tree from a snapshot written by `write_leaves`,
+    /// using `hasher` for any further `proof`/`proof_multi` calls
+    pub fn read_leaves(r: &mut impl Read, hasher: Box<dyn MerkleHasher>) -> std::io::Result<MerkleTree> {
+        let mut version = [0u8; 1];
+        r.read_exact(&mut version)?;
+        if version[0] != LEAVES_FORMAT_VERSION {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("unsupported leaves snapshot version {}", version[0])));
+        }
+
+        let mut sort_byte = [0u8; 1];
+        r.read_exact(&mut sort_byte)?;
+        let sort = sort_byte[0] != 0;
+
+        let count_leaves = read_u64(r)? as usize;
+        let mut leaves = Vec::with_capacity(count_leaves);
+        for _ in 0..count_leaves {
+            leaves.push(read_len_prefixed(r)?);
+        }
+
+        Ok(_build_from_leaves(leaves.as_slice(), sort, hasher))
+    }
+}
+
+/// [`verify_proof`] independently recomputes the root from `leaf` and its `proof` and
+/// compares it against `root`, mirroring the inclusion check an on-chain verifier does in `set_state`.
+/// Takes `hasher` rather than assuming [`Keccak256Hasher`], so a tree built with
+/// `MerkleTree::build_with_hasher` (e.g. over [`PoseidonHasher`]) can be verified too
+pub fn verify_proof(root: &Hash, leaf: &str, proof: &[(Hash, Side)], sort: bool, hasher: &dyn MerkleHasher) -> bool {
+    let mut node = hash_leaf(hasher, leaf);
+
+    for (sibling, side) in proof {
+        node = match side {
+            Side::Left => hash_internal_nodes(hasher, sibling, Some(&node), sort),
+            Side::Right => hash_internal_nodes(hasher, &node, Some(sibling), sort),
+        };
+    }
+
+    &node == root
+}
+
+/// [`PartialProof`] a compact multi-leaf inclusion proof: a depth-first bit per visited
+/// node (`true` = descend/matched, `false` = take the stored hash as-is) plus the minimal
+/// list of hashes needed to recompute the root, as used by Bitcoin's `merkleblock`
+#[derive(Debug, Clone)]
+pub struct PartialProof {
+    pub bits: Vec<bool>,
+    pub hashes: Vec<Hash>,
+    pub total_leaves: usize,
+}
+
+/// [`build_layers`] hashes `leaves` bottom-up into every level of the tree, so [`traverse_multi`]
+/// can decide what to include without re-hashing while walking
+fn build_layers(hasher: &dyn MerkleHasher, leaves: &[Hash], sort: bool) -> Vec<Vec<Hash>> {
+    let mut layers = vec![leaves.to_vec()];
+    while layers.last().unwrap().len() > 1 {
+        let next = build_upper_level(hasher, layers.last().unwrap(), sort);
+        layers.push(next);
+    }
+    layers
+}
+
+/// [`build_match_layers`] propagates which leaves were requested up through every level,
+/// pairing and padding exactly like [`build_upper_level`] so both stay index-aligned
+fn build_match_layers(leaf_matched: Vec<bool>) -> Vec<Vec<bool>> {
+    let mut layers = vec![leaf_matched];
+    while layers.last().unwrap().len() > 1 {
+        let prev = layers.last().unwrap();
+        let mut row = Vec::with_capacity((prev.len() + 1) / 2);
+        let mut i = 0;
+
+        while i < prev.len() {
+            if i + 1 < prev.len() {
+                row.push(prev[i] || prev[i + 1]);
+                i += 2;
+            } else {
+                row.push(prev[i]);
+                i += 1;
+            }
+        }
+
+        if row.len() > 1 && row.len() % 2 != 0 {
+            let last = *row.last().unwrap();
+            row.push(last);
+        }
+
+        layers.push(row);
+    }
+    layers
+}
+
+/// [`is_padding_duplicate`] tells whether `layers[height][index]` is a node `build_upper_level`
+/// appended only to make an odd row even (a bare copy of the real last node), rather than the
+/// hash of an actual pair — such a slot has no children of its own at `height - 1` to descend into
+fn is_padding_duplicate(layers: &[Vec<Hash>], height: usize, index: usize) -> bool {
+    if height == 0 {
+        return false;
+    }
+    let below_len = layers[height - 1].len();
+    let raw_count = (below_len + 1) / 2;
+    layers[height].len() == raw_count + 1 && index == layers[height].len() - 1
+}
+
+/// [`traverse_multi`] depth-first walk used by `proof_multi` to emit the bit/hash stream:
+/// an unmatched subtree contributes just its hash, a matched one descends further. A
+/// padding-duplicate slot (see `is_padding_duplicate`) is always treated as a leaf of the walk,
+/// since descending with `index * 2` would read past the end of the level below it
+fn traverse_multi(layers: &[Vec<Hash>], match_layers: &[Vec<bool>], height: usize, index: usize, bits: &mut Vec<bool>, hashes: &mut Vec<Hash>) {
+    let matched = match_layers[height][index];
+    bits.push(matched);
+
+    if !matched || height == 0 || is_padding_duplicate(layers, height, index) {
+        hashes.push(layers[height][index].clone());
+        return;
+    }
+
+    let left = index * 2;
+    let right = index * 2 + 1;
+    traverse_multi(layers, match_layers, height - 1, left, bits, hashes);
+    if right < layers[height - 1].len() {
+        traverse_multi(layers, match_layers, height - 1, right, bits, hashes);
+    }
+}
+
+/// [`layer_sizes`] replays the same pairing/padding rule `build_layers` used, so `verify_multi`
+/// knows the shape of the tree without having the original leaves
+fn layer_sizes(total_leaves: usize) -> Vec<usize> {
+    let mut sizes = vec![total_leaves];
+    while *sizes.last().unwrap() > 1 {
+        let prev = *sizes.last().unwrap();
+        let mut next = (prev + 1) / 2;
+        if next > 1 && next % 2 != 0 {
+            next += 1;
+        }
+        sizes.push(next);
+    }
+    sizes
+}
+
+/// [`is_padding_duplicate_size`] the `sizes`-only counterpart of `is_padding_duplicate`, for
+/// `traverse_verify_multi`, which has no leaf values to hash and so only ever sees level lengths
+fn is_padding_duplicate_size(sizes: &[usize], height: usize, index: usize) -> bool {
+    if height == 0 {
+        return false;
+    }
+    let below_len = sizes[height - 1];
+    let raw_count = (below_len + 1) / 2;
+    sizes[height] == raw_count + 1 && index == sizes[height] - 1
+}
+
+/// [`traverse_verify_multi`] mirrors `traverse_multi` but consumes the bit/hash stream to
+/// recompute each subtree's hash, collecting `(leaf index, hash)` for every matched leaf along the way.
+/// A padding-duplicate slot (see `is_padding_duplicate_size`) is always a leaf of the walk, matching
+/// `traverse_multi`; a genuine unpaired trailing node (no right sibling at all) passes its hash up
+/// unchanged, matching `hash_internal_nodes`'s `None`-right passthrough rather than hashing it with itself
+fn traverse_verify_multi(hasher: &dyn MerkleHasher, sizes: &[usize], height: usize, index: usize, sort: bool, bits: &[bool], hashes: &[Hash], bit_pos: &mut usize, hash_pos: &mut usize, matches: &mut Vec<(usize, Hash)>) -> Hash {
+    let matched = bits[*bit_pos];
+    *bit_pos += 1;
+
+    if !matched || height == 0 || is_padding_duplicate_size(sizes, height, index) {
+        let hash = hashes[*hash_pos].clone();
+        *hash_pos += 1;
+        if matched && height == 0 {
+            matches.push((index, hash.clone()));
+        }
+        return hash;
+    }
+
+    let left = traverse_verify_multi(hasher, sizes, height - 1, index * 2, sort, bits, hashes, bit_pos, hash_pos, matches);
+    if index * 2 + 1 < sizes[height - 1] {
+        let right = traverse_verify_multi(hasher, sizes, height - 1, index * 2 + 1, sort, bits, hashes, bit_pos, hash_pos, matches);
+        hash_internal_nodes(hasher, &left, Some(&right), sort)
+    } else {
+        left
+    }
+}
+
+/// [`verify_multi`] replays a [`PartialProof`]'s bit stream to recompute the root and returns the
+/// set of proven leaves (by position) if it matches `root`, or `None` if the proof is invalid.
+/// Takes `hasher` rather than assuming [`Keccak256Hasher`], so a tree built with
+/// `MerkleTree::build_with_hasher` (e.g. over [`PoseidonHasher`]) can be verified too
+pub fn verify_multi(root: &Hash, proof: &PartialProof, sort: bool, hasher: &dyn MerkleHasher) -> Option<Vec<(usize, Hash)>> {
+    let sizes = layer_sizes(proof.total_leaves);
+    let mut bit_pos = 0;
+    let mut hash_pos = 0;
+    let mut matches = Vec::new();
+
+    let computed_root = traverse_verify_multi(hasher, &sizes, sizes.len() - 1, 0, sort, &proof.bits, &proof.hashes, &mut bit_pos, &mut hash_pos, &mut matches);
+
+    if &computed_root == root {
+        Some(matches)
+    } else {
+        None
+    }
+}
+
+/// [`IncrementalMerkleTree`] structure definition
+///
+/// Unlike [`MerkleTree`], which is rebuilt from scratch every time a leaf is
+/// added, this tree only keeps the "frontier" of the tree — the left-sibling
+/// hash at every level along the rightmost path — so a single [`IncrementalMerkleTree::append`]
+/// costs O(log n) instead of re-hashing the whole leaf set.
+#[derive(Debug)]
+pub struct IncrementalMerkleTree {
+    ommers: Vec<Option<Hash>>,
+    position: usize,
+    depth: usize,
+    sort: bool,
+    hasher: Box<dyn MerkleHasher>,
+    /// `empty_roots[level]` is the root hash of an empty subtree of that height, precomputed once
+    /// when the tree is created (`new_with_hasher` or `read_frontier`) so `root_hash` is O(depth)
+    /// instead of recomputing each level from scratch
+    empty_roots: Vec<Hash>,
+}
+
+/// [`build_empty_roots`] precomputes `empty_root(0..=depth)`: index `0` is the zero leaf itself
+/// (32 zero bytes, not hashed), index `k` is `hash_internal_nodes` of two index `k - 1` entries
+fn build_empty_roots(hasher: &dyn MerkleHasher, depth: usize, sort: bool) -> Vec<Hash> {
+    let mut roots = Vec::with_capacity(depth + 1);
+    roots.push(vec![0u8; 32]);
+    for level in 1..=depth {
+        let prev = &roots[level - 1];
+        roots.push(hash_internal_nodes(hasher, prev, Some(prev), sort));
+    }
+    roots
+}
+
+impl IncrementalMerkleTree {
+    /// [`IncrementalMerkleTree::new`] creates an empty tree able to hold up to `2^depth` leaves, hashing with the default [`Keccak256Hasher`]
+    pub fn new(depth: usize, sort: bool) -> IncrementalMerkleTree {
+        IncrementalMerkleTree::new_with_hasher(depth, sort, Box::new(Keccak256Hasher))
+    }
+
+    /// [`IncrementalMerkleTree::new_with_hasher`] creates an empty tree using the given [`MerkleHasher`]
+    pub fn new_with_hasher(depth: usize, sort: bool, hasher: Box<dyn MerkleHasher>) -> IncrementalMerkleTree {
+        let empty_roots = build_empty_roots(hasher.as_ref(), depth, sort);
+
+        IncrementalMerkleTree {
+            ommers: vec![None; depth],
+            position: 0,
+            depth: depth,
+            sort: sort,
+            hasher: hasher,
+            empty_roots: empty_roots,
+        }
+    }
+
+    /// [`IncrementalMerkleTree::empty_root`] returns the root hash of an empty subtree of `level` height,
+    /// read from the `empty_roots` table precomputed when the tree was created
+    fn empty_root(&self, level: usize) -> Hash {
+        self.empty_roots[level].clone()
+    }
+
+    /// [`IncrementalMerkleTree::append`] hashes `leaf` and folds it into the frontier, growing `position` by one.
+    ///
+    /// Panics once the tree is at full capacity (`2^depth` leaves already appended): past that
+    /// point every bit `root_hash` inspects is zero again, so it would silently fold in empty-subtree
+    /// hashes as if the tree were still empty and return the wrong root instead of erroring
+    pub fn append(&mut self, leaf: &str) {
+        assert!(self.position < (1usize << self.depth), "IncrementalMerkleTree is at full capacity ({} leaves)", self.position);
+
+        let mut node = hash_leaf(self.hasher.as_ref(), leaf);
+        let mut position = self.position;
+
+        for level in 0..self.depth {
+            if position & 1 == 1 {
+                let left = self.ommers[level].take().expect("missing ommer for a completed left subtree");
+                node = hash_internal_nodes(self.hasher.as_ref(), &left, Some(&node), self.sort);
+            } else {
+                self.ommers[level] = Some(node);
+                break;
+            }
+            position >>= 1;
+        }
+
+        self.position += 1;
+    }
+
+    /// [`IncrementalMerkleTree::root_hash`] folds the current frontier with the empty-subtree hashes to get the root
+    pub fn root_hash(&self) -> Hash {
+        let mut root = self.empty_root(0);
+        let mut position = self.position;
+
+        for level in 0..self.depth {
+            root = if position & 1 == 1 {
+                hash_internal_nodes(self.hasher.as_ref(), self.ommers[level].as_ref().expect("missing ommer for a completed left subtree"), Some(&root), self.sort)
+            } else {
+                hash_internal_nodes(self.hasher.as_ref(), &root, Some(&self.empty_root(level)), self.sort)
+            };
+            position >>= 1;
+        }
+
+        root
+    }
+
+    /// [`IncrementalMerkleTree::proof`] rebuilds the inclusion proof for the leaf at `index` from
+    /// `leaves` (every value appended so far, in the same insertion order passed to `append`),
+    /// padding with `empty_root` wherever a sibling subtree has no real leaf in it yet. Unlike
+    /// `MerkleTree::proof`, this always walks a fixed `depth` levels and never sorts the leaf set,
+    /// so the result verifies against this tree's own `root_hash`
+    pub fn proof(&self, leaves: &[&str], index: usize) -> Vec<(Hash, Side)> {
+        let mut level: Vec<Hash> = leaves.iter().map(|v| hash_leaf(self.hasher.as_ref(), v)).collect();
+        let mut idx = index;
+        let mut proof = Vec::with_capacity(self.depth);
+
+        for l in 0..self.depth {
+            let side = if idx % 2 == 0 { Side::Right } else { Side::Left };
+            let sibling = level.get(idx ^ 1).cloned().unwrap_or_else(|| self.empty_root(l));
+            proof.push((sibling, side));
+
+            let mut next = Vec::with_capacity((level.len() + 1) / 2);
+            let mut i = 0;
+            while i < level.len() {
+                let right = level.get(i + 1).cloned().unwrap_or_else(|| self.empty_root(l));
+                next.push(hash_internal_nodes(self.hasher.as_ref(), &level[i], Some(&right), self.sort));
+                i += 2;
+            }
+            level = next;
+            idx >>= 1;
         }
 
         proof
     }
+
+    /// [`IncrementalMerkleTree::root_hash_str`] to return root hash as hex string
+    pub fn root_hash_str(&self) -> String {
+        use rustc_serialize::hex::ToHex;
+        self.root_hash().as_slice().to_hex()
+    }
+
+    /// [`IncrementalMerkleTree::position`] returns the number of leaves appended so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// [`IncrementalMerkleTree::write_frontier`] serializes the tree's minimal state — the
+    /// `ommers` frontier, `position` and `depth` — so it can be restored without replaying every
+    /// `append`. Format: version byte, depth (u64 LE), position (u64 LE), then one presence byte
+    /// per level followed by a length-prefixed hash whenever that ommer is set
+    pub fn write_frontier(&self, w: &mut impl Write) -> std::io::Result<()> {
+        w.write_all(&[FRONTIER_FORMAT_VERSION])?;
+        w.write_all(&(self.depth as u64).to_le_bytes())?;
+        w.write_all(&(self.position as u64).to_le_bytes())?;
+
+        for ommer in &self.ommers {
+            match ommer {
+                Some(hash) => {
+                    w.write_all(&[1u8])?;
+                    write_len_prefixed(w, hash)?;
+                }
+                None => w.write_all(&[0u8])?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// [`IncrementalMerkleTree::read_frontier`] restores a tree previously written by
+    /// `write_frontier`, using `sort`/`hasher` for any further `append`/`root_hash` calls
+    pub fn read_frontier(r: &mut impl Read, sort: bool, hasher: Box<dyn MerkleHasher>) -> std::io::Result<IncrementalMerkleTree> {
+        let mut version = [0u8; 1];
+        r.read_exact(&mut version)?;
+        if version[0] != FRONTIER_FORMAT_VERSION {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("unsupported frontier snapshot version {}", version[0])));
+        }
+
+        let depth = read_u64(r)? as usize;
+        let position = read_u64(r)? as usize;
+
+        let mut ommers = Vec::with_capacity(depth);
+        for _ in 0..depth {
+            let mut present = [0u8; 1];
+            r.read_exact(&mut present)?;
+            ommers.push(if present[0] == 1 { Some(read_len_prefixed(r)?) } else { None });
+        }
+
+        let empty_roots = build_empty_roots(hasher.as_ref(), depth, sort);
+
+        Ok(IncrementalMerkleTree { ommers: ommers, position: position, depth: depth, sort: sort, hasher: hasher, empty_roots: empty_roots })
+    }
 }
 
 /// Just simple test for `MerkleTree`
@@ -208,4 +703,155 @@ mod tests {
         //            t.root_hash_str());
     }
 
+    #[test]
+    fn test_incremental_append_advances_position_and_root() {
+        use super::IncrementalMerkleTree;
+
+        let address2 = "f17f52151EbEF6C7334FAD080c5704D77216b732";
+        let address3 = "C5fdf4076b8F3A5357c5E395ab970B5B54098Fef";
+        let address1 = "821aEa9a577a9b44299B9c15c88cf3087F3b5544";
+
+        let mut imt = IncrementalMerkleTree::new(32, true);
+        let root_before = imt.root_hash_str();
+
+        imt.append(address2);
+        imt.append(address3);
+        imt.append(address1);
+
+        assert_eq!(3, imt.position());
+        assert_ne!(root_before, imt.root_hash_str());
+    }
+
+    #[test]
+    fn test_poseidon_hasher_differs_from_keccak256() {
+        use super::{Keccak256Hasher, PoseidonHasher};
+
+        let address2 = "f17f52151EbEF6C7334FAD080c5704D77216b732";
+        let address3 = "C5fdf4076b8F3A5357c5E395ab970B5B54098Fef";
+        let address1 = "821aEa9a577a9b44299B9c15c88cf3087F3b5544";
+
+        let mut keccak_tree = MerkleTree::build_with_hasher(&[address2, address3, address1], true, Box::new(Keccak256Hasher));
+        let mut poseidon_tree = MerkleTree::build_with_hasher(&[address2, address3, address1], true, Box::new(PoseidonHasher));
+
+        assert_ne!(keccak_tree.root_hash_str(), poseidon_tree.root_hash_str());
+    }
+
+    #[test]
+    fn test_verify_proof_roundtrip() {
+        use super::{verify_proof, Keccak256Hasher};
+
+        let address2 = "f17f52151EbEF6C7334FAD080c5704D77216b732";
+        let address3 = "C5fdf4076b8F3A5357c5E395ab970B5B54098Fef";
+        let address1 = "821aEa9a577a9b44299B9c15c88cf3087F3b5544";
+
+        let mut t: MerkleTree = MerkleTree::build(&[address2, address3, address1], true);
+        let proof = t.proof(address1, -1);
+
+        assert!(verify_proof(t.root_hash(), address1, &proof, true, &Keccak256Hasher));
+        assert!(!verify_proof(t.root_hash(), address2, &proof, true, &Keccak256Hasher));
+    }
+
+    #[test]
+    fn test_proof_multi_roundtrip() {
+        use super::{verify_multi, Keccak256Hasher};
+
+        let address2 = "f17f52151EbEF6C7334FAD080c5704D77216b732";
+        let address3 = "C5fdf4076b8F3A5357c5E395ab970B5B54098Fef";
+        let address1 = "821aEa9a577a9b44299B9c15c88cf3087F3b5544";
+
+        let t: MerkleTree = MerkleTree::build(&[address2, address3, address1], true);
+        let proof = t.proof_multi(&[0, 2]);
+
+        let matches = verify_multi(t.root_hash(), &proof, true, &Keccak256Hasher).expect("proof should verify against the root");
+        let matched_indices: Vec<usize> = matches.iter().map(|(index, _hash)| *index).collect();
+
+        assert_eq!(vec![0, 2], matched_indices);
+        assert_eq!(t.leaves()[0], matches[0].1);
+        assert_eq!(t.leaves()[2], matches[1].1);
+    }
+
+    /// [`make_addresses`] deterministic, distinct hex-string leaf values for sweep tests:
+    /// `count` 20-byte values that don't collide under keccak256
+    fn make_addresses(count: usize) -> Vec<String> {
+        (0..count).map(|i| format!("{:040x}", i + 1)).collect()
+    }
+
+    #[test]
+    fn test_proof_roundtrip_sweep() {
+        use super::{verify_proof, Keccak256Hasher};
+
+        for count in 2..=40 {
+            let addresses = make_addresses(count);
+            let values: Vec<&str> = addresses.iter().map(|s| s.as_str()).collect();
+            let mut t: MerkleTree = MerkleTree::build(&values, false);
+
+            for index in 0..count {
+                let proof = t.proof(values[index], -1);
+                assert!(
+                    verify_proof(t.root_hash(), values[index], &proof, false, &Keccak256Hasher),
+                    "proof for leaf {} of {} should verify", index, count
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_proof_multi_roundtrip_sweep() {
+        use super::{verify_multi, Keccak256Hasher};
+
+        for count in 2..=40 {
+            let addresses = make_addresses(count);
+            let values: Vec<&str> = addresses.iter().map(|s| s.as_str()).collect();
+            let t: MerkleTree = MerkleTree::build(&values, false);
+
+            let indices: Vec<usize> = (0..count).step_by(2).collect();
+            let proof = t.proof_multi(&indices);
+
+            let matches = verify_multi(t.root_hash(), &proof, false, &Keccak256Hasher)
+                .unwrap_or_else(|| panic!("multi-proof for {} leaves should verify", count));
+            let matched_indices: Vec<usize> = matches.iter().map(|(index, _hash)| *index).collect();
+
+            assert_eq!(indices, matched_indices, "wrong matched indices for {} leaves", count);
+            for (index, hash) in &matches {
+                assert_eq!(t.leaves()[*index], *hash, "wrong leaf hash for index {} of {}", index, count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_leaves_snapshot_roundtrip() {
+        let address2 = "f17f52151EbEF6C7334FAD080c5704D77216b732";
+        let address3 = "C5fdf4076b8F3A5357c5E395ab970B5B54098Fef";
+        let address1 = "821aEa9a577a9b44299B9c15c88cf3087F3b5544";
+
+        let t: MerkleTree = MerkleTree::build(&[address2, address3, address1], true);
+
+        let mut snapshot = Vec::new();
+        t.write_leaves(&mut snapshot).expect("writing a leaves snapshot should not fail");
+
+        let restored = MerkleTree::read_leaves(&mut snapshot.as_slice(), Box::new(super::Keccak256Hasher)).expect("reading the snapshot back should not fail");
+        assert_eq!(t.root_hash_str(), restored.root_hash_str());
+    }
+
+    #[test]
+    fn test_frontier_snapshot_roundtrip() {
+        use super::IncrementalMerkleTree;
+
+        let address2 = "f17f52151EbEF6C7334FAD080c5704D77216b732";
+        let address3 = "C5fdf4076b8F3A5357c5E395ab970B5B54098Fef";
+        let address1 = "821aEa9a577a9b44299B9c15c88cf3087F3b5544";
+
+        let mut imt = IncrementalMerkleTree::new(32, true);
+        imt.append(address2);
+        imt.append(address3);
+        imt.append(address1);
+
+        let mut snapshot = Vec::new();
+        imt.write_frontier(&mut snapshot).expect("writing a frontier snapshot should not fail");
+
+        let restored = IncrementalMerkleTree::read_frontier(&mut snapshot.as_slice(), true, Box::new(super::Keccak256Hasher)).expect("reading the snapshot back should not fail");
+        assert_eq!(imt.position(), restored.position());
+        assert_eq!(imt.root_hash_str(), restored.root_hash_str());
+    }
+
 }
\ No newline at end of file